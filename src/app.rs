@@ -1,4 +1,6 @@
-use std::time::Instant;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use color_eyre::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
@@ -8,105 +10,356 @@ use ratatui::style::Stylize;
 use ratatui::{
     layout::{Alignment, Direction, Layout},
     style::{Color, Modifier, Style},
-    text::Line,
-    widgets::{Block, Paragraph},
+    symbols,
+    text::{Line, Span},
+    widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph},
     DefaultTerminal, Frame,
 };
 
+/// Embedded fallback word list, used when no custom list is supplied.
+const DEFAULT_WORD_LIST: &str = include_str!("words.txt");
+
+/// How many words `Timed` mode generates up front, and adds on each top-up.
+const TIMED_LOOKAHEAD_WORDS: usize = 15;
+/// Once fewer than this many target chars remain ahead of the caret, top up.
+const TIMED_LOOKAHEAD_CHARS: usize = 30;
+/// How long `handle_crossterm_events` waits for input before returning, so
+/// the run loop still ticks (countdown, timed end-of-test) while idle.
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+/// Classifies a single position in the target/typed alignment produced by
+/// `App::char_alignment`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharStatus {
+    Correct,
+    Incorrect,
+    /// Part of the target text the user hasn't reached yet.
+    Untyped,
+    /// Typed past the end of the target text.
+    Overflow,
+}
+
+/// Summary stats computed once a test finishes, shown on the results screen.
+#[derive(Debug, Clone, Copy)]
+struct TestResult {
+    wpm: f64,
+    raw_wpm: f64,
+    accuracy: f64,
+    consistency: f64,
+}
+
+/// Selects how a test is generated and when it ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestMode {
+    /// Ends once the given number of words has been typed.
+    WordCount(usize),
+    /// Words are generated endlessly; the test ends when the duration elapses.
+    Timed(Duration),
+}
+
+impl Default for TestMode {
+    fn default() -> Self {
+        TestMode::WordCount(10)
+    }
+}
+
+/// Restores the terminal on drop, so a panic unwinding out of `run` can't
+/// leave raw mode / the alternate screen enabled.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
 #[derive(Debug)]
 pub struct App {
     running: bool,
+    mode: TestMode,
+    /// Pool of candidate words the active test is drawn from.
+    word_list: Vec<String>,
     words: Vec<String>,
     input: String,
+    /// Caret position, expressed as a char index into `input`.
+    cursor: usize,
     start_time: Option<Instant>,
     wpm_data: Vec<u32>,
+    /// The last whole second `wpm_data` was sampled for, so repeated ticks
+    /// within the same second don't push duplicate samples.
+    last_sampled_second: u64,
+    /// Set once the test finishes: in `WordCount` mode when the typed input
+    /// reaches the target text length, in `Timed` mode when the clock expires.
+    result: Option<TestResult>,
 }
 
 impl Default for App {
     fn default() -> Self {
+        Self::with_mode(TestMode::default(), None)
+    }
+}
+
+impl App {
+    /// Builds an app for the given mode, loading words from `word_list_path`
+    /// if given (falling back to the embedded default list on any error).
+    pub fn with_mode(mode: TestMode, word_list_path: Option<PathBuf>) -> Self {
+        let word_list = Self::load_word_list(word_list_path.as_deref());
+        let words = Self::generate_words(&word_list, mode);
         Self {
             running: false,
-            words: Self::generate_text(),
+            mode,
+            word_list,
+            words,
             input: String::new(),
+            cursor: 0,
             start_time: None,
             wpm_data: Vec::new(),
+            last_sampled_second: 0,
+            result: None,
         }
     }
-}
 
-impl App {
-    fn generate_text() -> Vec<String> {
-        let word_list = [
-            "hello",
-            "world",
-            "rust",
-            "speed",
-            "test",
-            "keyboard",
-            "fast",
-            "typing",
-            "game",
-            "challenge",
-            "performance",
-            "accuracy",
-        ];
-        let mut rng = rand::rng();
-        word_list
-            .choose_multiple(&mut rng, 10)
-            .map(|s| s.to_string())
+    fn load_word_list(path: Option<&Path>) -> Vec<String> {
+        let contents = path
+            .and_then(|p| fs::read_to_string(p).ok())
+            .unwrap_or_else(|| DEFAULT_WORD_LIST.to_string());
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
             .collect()
     }
 
+    fn generate_words(word_list: &[String], mode: TestMode) -> Vec<String> {
+        let mut rng = rand::rng();
+        match mode {
+            TestMode::WordCount(count) => word_list
+                .choose_multiple(&mut rng, count)
+                .cloned()
+                .collect(),
+            TestMode::Timed(_) => (0..TIMED_LOOKAHEAD_WORDS)
+                .filter_map(|_| word_list.choose(&mut rng).cloned())
+                .collect(),
+        }
+    }
+
+    /// In `Timed` mode, keeps generating words ahead of the caret so the
+    /// test never runs out of text before the clock does.
+    fn ensure_words_ahead(&mut self) {
+        if !matches!(self.mode, TestMode::Timed(_)) {
+            return;
+        }
+        let remaining = self
+            .target_text()
+            .chars()
+            .count()
+            .saturating_sub(self.cursor);
+        if remaining < TIMED_LOOKAHEAD_CHARS {
+            let mut rng = rand::rng();
+            for _ in 0..TIMED_LOOKAHEAD_WORDS {
+                if let Some(word) = self.word_list.choose(&mut rng) {
+                    self.words.push(word.clone());
+                }
+            }
+        }
+    }
+
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Aligns `input` against the flat target text (words joined by spaces)
+    /// char-by-char, so variable-length words line up correctly instead of
+    /// assuming uniform word lengths. Positions are compared one-to-one:
+    /// target chars not yet reached are `Untyped` and chars typed past the
+    /// end are `Overflow`, but a char skipped or inserted mid-text shifts the
+    /// comparison of every later position.
+    fn char_alignment(&self) -> Vec<(char, CharStatus)> {
+        let target: Vec<char> = self.target_text().chars().collect();
+        let typed: Vec<char> = self.input.chars().collect();
+        let len = target.len().max(typed.len());
+
+        (0..len)
+            .map(|i| match (target.get(i), typed.get(i)) {
+                (Some(&t), Some(&c)) if t == c => (t, CharStatus::Correct),
+                (Some(&t), Some(_)) => (t, CharStatus::Incorrect),
+                (Some(&t), None) => (t, CharStatus::Untyped),
+                (None, Some(&c)) => (c, CharStatus::Overflow),
+                (None, None) => unreachable!(),
+            })
+            .collect()
+    }
+
+    /// Renders the target text as a single styled line: matched chars green,
+    /// mismatched red, the untyped remainder dim, and any overflow the user
+    /// typed past the target underlined red. The caret is drawn as a
+    /// reversed cell over whatever's at that position.
+    fn styled_lines(&self) -> Vec<Line<'static>> {
+        let alignment = self.char_alignment();
+
+        let mut spans: Vec<Span<'static>> = alignment
+            .into_iter()
+            .enumerate()
+            .map(|(i, (c, status))| {
+                let mut style = match status {
+                    CharStatus::Correct => Style::default().fg(Color::Green),
+                    CharStatus::Incorrect => Style::default().fg(Color::Red),
+                    CharStatus::Untyped => Style::default().fg(Color::DarkGray),
+                    CharStatus::Overflow => Style::default()
+                        .fg(Color::Red)
+                        .add_modifier(Modifier::UNDERLINED),
+                };
+                if i == self.cursor {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Span::styled(c.to_string(), style)
+            })
+            .collect();
+
+        if self.cursor >= spans.len() {
+            spans.push(Span::styled(
+                " ",
+                Style::default().add_modifier(Modifier::REVERSED),
+            ));
+        }
+
+        vec![Line::from(spans)]
+    }
+
+    /// Wraps the default panic hook so a panic while the terminal is in raw
+    /// mode / the alternate screen still leaves a readable terminal behind.
+    fn install_panic_hook() {
+        let original_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic_info| {
+            ratatui::restore();
+            original_hook(panic_info);
+        }));
+    }
+
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        Self::install_panic_hook();
+        let _guard = TerminalGuard;
         self.running = true;
         while self.running {
             terminal.draw(|frame| self.draw(frame))?;
             self.handle_crossterm_events()?;
-            self.update_wpm();
+            if self.result.is_none() {
+                self.ensure_words_ahead();
+                self.update_wpm();
+                self.check_finished();
+            }
         }
         Ok(())
     }
 
     fn draw(&mut self, frame: &mut Frame) {
+        match self.result {
+            Some(result) => self.draw_results(frame, result),
+            None => self.draw_typing(frame),
+        }
+    }
+
+    fn draw_results(&self, frame: &mut Frame, result: TestResult) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(3)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(frame.area());
+
+        let stats_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+            ])
+            .split(layout[0]);
+
+        let stat = |title: &'static str, value: String| {
+            Paragraph::new(value)
+                .block(Block::bordered().title(title))
+                .alignment(Alignment::Center)
+                .style(Style::default().add_modifier(Modifier::BOLD))
+        };
+
+        frame.render_widget(stat("WPM", format!("{:.0}", result.wpm)), stats_area[0]);
+        frame.render_widget(
+            stat("Raw WPM", format!("{:.0}", result.raw_wpm)),
+            stats_area[1],
+        );
+        frame.render_widget(
+            stat("Accuracy", format!("{:.0}%", result.accuracy)),
+            stats_area[2],
+        );
+        frame.render_widget(
+            stat("Consistency", format!("{:.0}%", result.consistency)),
+            stats_area[3],
+        );
+
+        let points: Vec<(f64, f64)> = self
+            .wpm_data
+            .iter()
+            .enumerate()
+            .map(|(i, &wpm)| ((i + 1) as f64, wpm as f64))
+            .collect();
+
+        let max_time = points.last().map_or(1.0, |(x, _)| *x).max(1.0);
+        let max_wpm = self.wpm_data.iter().copied().max().unwrap_or(0).max(10) as f64;
+
+        let dataset = Dataset::default()
+            .name("WPM")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&points);
+
+        let chart = Chart::new(vec![dataset])
+            .block(Block::bordered().title("WPM over time (Ctrl+R to restart)"))
+            .x_axis(
+                Axis::default()
+                    .title("seconds")
+                    .bounds([0.0, max_time])
+                    .labels([Line::from("0"), Line::from(format!("{max_time:.0}"))]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title("wpm")
+                    .bounds([0.0, max_wpm])
+                    .labels([Line::from("0"), Line::from(format!("{max_wpm:.0}"))]),
+            );
+
+        frame.render_widget(chart, layout[1]);
+    }
+
+    fn draw_typing(&mut self, frame: &mut Frame) {
         let layout = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(100)])
             .margin(3)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(3),
+                Constraint::Length(3),
+            ])
             .split(frame.area());
 
+        let bottom = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(layout[2]);
+
         let title = Line::from("Teaty Typing Speed Test")
             .bold()
             .blue()
             .centered();
 
-        let mut text_display = String::new();
-        for (i, word) in self.words.iter().enumerate() {
-            if i > 0 {
-                text_display.push(' ');
-            }
-            for (j, c) in word.chars().enumerate() {
-                if let Some(input_char) = self.input.chars().nth(i * (word.len() + 1) + j) {
-                    if input_char == c {
-                        text_display.push_str(&c.to_string().green().to_string());
-                    } else {
-                        text_display.push_str(&c.to_string().red().to_string());
-                    }
-                } else {
-                    text_display.push(c);
-                }
-            }
-        }
-
         let wpm_display = format!("WPM: {}", self.wpm_data.last().unwrap_or(&0));
+        let mode_display = self.mode_progress_display();
 
-        let text_paragraph = Paragraph::new(text_display)
+        let text_paragraph = Paragraph::new(self.styled_lines())
             .block(Block::bordered().title("Words to Type"))
-            .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::LightGreen));
+            .alignment(Alignment::Center);
 
         let wpm_paragraph = Paragraph::new(wpm_display)
             .block(Block::bordered().title("Speed (WPM)"))
@@ -117,15 +370,51 @@ impl App {
                     .add_modifier(Modifier::BOLD),
             );
 
+        let mode_paragraph = Paragraph::new(mode_display)
+            .block(Block::bordered().title("Progress"))
+            .alignment(Alignment::Right)
+            .style(
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            );
+
         frame.render_widget(
             Paragraph::new("").block(Block::bordered().title(title)),
             layout[0],
         );
-        frame.render_widget(text_paragraph, layout[0]);
-        frame.render_widget(wpm_paragraph, layout[0]);
+        frame.render_widget(text_paragraph, layout[1]);
+        frame.render_widget(wpm_paragraph, bottom[0]);
+        frame.render_widget(mode_paragraph, bottom[1]);
+    }
+
+    /// A countdown for `Timed` mode, or a word-count progress indicator for
+    /// `WordCount` mode.
+    fn mode_progress_display(&self) -> String {
+        match self.mode {
+            TestMode::WordCount(total) => {
+                // A word only counts once its trailing space has been typed,
+                // so the current, still-in-progress word isn't counted yet.
+                let completed_words = self.input.matches(' ').count().min(total);
+                format!("Words: {completed_words}/{total}")
+            }
+            TestMode::Timed(duration) => {
+                let remaining = self
+                    .start_time
+                    .map(|start| duration.saturating_sub(start.elapsed()))
+                    .unwrap_or(duration);
+                format!("Time left: {}s", remaining.as_secs())
+            }
+        }
     }
 
+    /// Polls for input instead of blocking on it, so the run loop keeps
+    /// ticking (and the timed countdown keeps advancing) even while the
+    /// user isn't pressing anything.
     fn handle_crossterm_events(&mut self) -> Result<()> {
+        if !event::poll(TICK_RATE)? {
+            return Ok(());
+        }
         match event::read()? {
             Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
             Event::Mouse(_) => {}
@@ -137,24 +426,128 @@ impl App {
 
     // All keystrokes along with the exit logic implemented
     fn on_key_event(&mut self, key: KeyEvent) {
+        if self.result.is_some() {
+            match (key.modifiers, key.code) {
+                (_, KeyCode::Esc)
+                | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+                (KeyModifiers::CONTROL, KeyCode::Char('r')) => self.reset(),
+                _ => {}
+            }
+            return;
+        }
+        if self.start_time.is_none() {
+            if let Some(mode) = Self::mode_for_key(key.code) {
+                self.set_mode(mode);
+                return;
+            }
+        }
         match (key.modifiers, key.code) {
             (_, KeyCode::Esc)
             | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
-            (KeyModifiers::NONE, KeyCode::Char(c)) => {
-                if self.start_time.is_none() {
-                    self.start_time = Some(Instant::now());
-                }
-                self.input.push(c);
+            (KeyModifiers::NONE, KeyCode::Char(c)) => self.insert_char(c),
+            (KeyModifiers::ALT, KeyCode::Backspace)
+            | (KeyModifiers::CONTROL, KeyCode::Char('w') | KeyCode::Char('W')) => {
+                self.delete_word_before_cursor()
+            }
+            (_, KeyCode::Backspace) => self.delete_char_before_cursor(),
+            (_, KeyCode::Left) => self.move_cursor_left(),
+            (_, KeyCode::Right) => self.move_cursor_right(),
+            (KeyModifiers::CONTROL, KeyCode::Char('u') | KeyCode::Char('U')) => {
+                self.clear_before_cursor()
             }
             (KeyModifiers::CONTROL, KeyCode::Char('r')) => self.reset(),
             _ => {}
         }
     }
 
+    /// Maps the mode-select hotkeys (F1-F3 for word counts, F4-F6 for timed
+    /// runs) to the `TestMode` they select.
+    fn mode_for_key(code: KeyCode) -> Option<TestMode> {
+        match code {
+            KeyCode::F(1) => Some(TestMode::WordCount(10)),
+            KeyCode::F(2) => Some(TestMode::WordCount(25)),
+            KeyCode::F(3) => Some(TestMode::WordCount(50)),
+            KeyCode::F(4) => Some(TestMode::Timed(Duration::from_secs(15))),
+            KeyCode::F(5) => Some(TestMode::Timed(Duration::from_secs(30))),
+            KeyCode::F(6) => Some(TestMode::Timed(Duration::from_secs(60))),
+            _ => None,
+        }
+    }
+
+    /// Byte offset of the `char_idx`-th char in `input`, or its length if
+    /// `char_idx` is past the end.
+    fn byte_index_at(&self, char_idx: usize) -> usize {
+        self.input
+            .char_indices()
+            .nth(char_idx)
+            .map(|(i, _)| i)
+            .unwrap_or(self.input.len())
+    }
+
+    fn insert_char(&mut self, c: char) {
+        if self.start_time.is_none() {
+            self.start_time = Some(Instant::now());
+        }
+        let byte_idx = self.byte_index_at(self.cursor);
+        self.input.insert(byte_idx, c);
+        self.cursor += 1;
+    }
+
+    fn delete_char_before_cursor(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        let start = self.byte_index_at(self.cursor - 1);
+        let end = self.byte_index_at(self.cursor);
+        self.input.replace_range(start..end, "");
+        self.cursor -= 1;
+    }
+
+    fn move_cursor_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    fn move_cursor_right(&mut self) {
+        let len = self.input.chars().count();
+        if self.cursor < len {
+            self.cursor += 1;
+        }
+    }
+
+    fn clear_before_cursor(&mut self) {
+        let end = self.byte_index_at(self.cursor);
+        self.input.replace_range(0..end, "");
+        self.cursor = 0;
+    }
+
+    /// Deletes the whitespace-delimited word immediately before the caret,
+    /// mirroring a shell's Ctrl+W.
+    fn delete_word_before_cursor(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        let chars: Vec<char> = self.input.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        let start_byte = self.byte_index_at(start);
+        let end_byte = self.byte_index_at(self.cursor);
+        self.input.replace_range(start_byte..end_byte, "");
+        self.cursor = start;
+    }
+
     fn update_wpm(&mut self) {
         if let Some(start) = self.start_time {
             let elapsed = start.elapsed().as_secs();
-            if elapsed > 0 {
+            // Sample once per whole second: the chart and consistency score
+            // both assume one data point per elapsed second, not per tick.
+            if elapsed > 0 && elapsed > self.last_sampled_second {
+                self.last_sampled_second = elapsed;
+
                 // Calculate words per minute (WPM)
                 // WPM is calculated as the number of characters typed divided by 5 (average word length)
                 // multiplied by 60 (seconds in a minute) divided by the elapsed time in seconds
@@ -167,10 +560,93 @@ impl App {
     }
 
     fn reset(&mut self) {
-        self.words = Self::generate_text();
+        self.words = Self::generate_words(&self.word_list, self.mode);
         self.input.clear();
+        self.cursor = 0;
         self.start_time = None;
         self.wpm_data.clear();
+        self.last_sampled_second = 0;
+        self.result = None;
+    }
+
+    fn set_mode(&mut self, mode: TestMode) {
+        self.mode = mode;
+        self.reset();
+    }
+
+    fn target_text(&self) -> String {
+        self.words.join(" ")
+    }
+
+    fn check_finished(&mut self) {
+        let done = match self.mode {
+            TestMode::WordCount(_) => {
+                self.input.chars().count() >= self.target_text().chars().count()
+            }
+            TestMode::Timed(duration) => self
+                .start_time
+                .is_some_and(|start| start.elapsed() >= duration),
+        };
+        if done {
+            self.finish();
+        }
+    }
+
+    fn finish(&mut self) {
+        let elapsed = match self.mode {
+            TestMode::Timed(duration) => duration.as_secs_f64(),
+            TestMode::WordCount(_) => self
+                .start_time
+                .map(|start| start.elapsed().as_secs_f64())
+                .unwrap_or(0.0),
+        }
+        .max(1.0 / 60.0);
+        let minutes = elapsed / 60.0;
+
+        let alignment = self.char_alignment();
+        let typed_chars = alignment
+            .iter()
+            .filter(|(_, status)| *status != CharStatus::Untyped)
+            .count();
+        let correct_chars = alignment
+            .iter()
+            .filter(|(_, status)| *status == CharStatus::Correct)
+            .count();
+
+        let raw_wpm = (typed_chars as f64 / 5.0) / minutes;
+        let wpm = (correct_chars as f64 / 5.0) / minutes;
+        let accuracy = if typed_chars > 0 {
+            100.0 * correct_chars as f64 / typed_chars as f64
+        } else {
+            0.0
+        };
+        let consistency = Self::consistency_score(&self.wpm_data);
+
+        self.result = Some(TestResult {
+            wpm,
+            raw_wpm,
+            accuracy,
+            consistency,
+        });
+    }
+
+    /// A consistency score derived from how much the per-second WPM samples
+    /// vary: `100 * (1 - stddev/mean)`, clamped to 0-100.
+    fn consistency_score(samples: &[u32]) -> f64 {
+        if samples.is_empty() {
+            return 100.0;
+        }
+        let mean = samples.iter().sum::<u32>() as f64 / samples.len() as f64;
+        if mean == 0.0 {
+            return 100.0;
+        }
+        let variance = samples
+            .iter()
+            .map(|&s| (s as f64 - mean).powi(2))
+            .sum::<f64>()
+            / samples.len() as f64;
+        let stddev = variance.sqrt();
+        (100.0 * (1.0 - stddev / mean)).clamp(0.0, 100.0)
     }
 
     fn quit(&mut self) {